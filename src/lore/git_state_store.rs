@@ -0,0 +1,110 @@
+//! Git-backed alternative to the plain JSON `save_*`/`load_*` files in `lore_session`.
+
+use git2::{Repository, Signature};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const STATE_REF: &str = "refs/patch-hub/state";
+
+#[derive(Error, Debug)]
+pub enum GitStateStoreError {
+    #[error(transparent)]
+    FromGit2(#[from] git2::Error),
+    #[error(transparent)]
+    FromSerdeJson(#[from] serde_json::Error),
+}
+
+impl From<GitStateStoreError> for io::Error {
+    fn from(error: GitStateStoreError) -> Self {
+        io::Error::other(error)
+    }
+}
+
+/// A dedicated local git repository used as an append-only key/value store: each
+/// key is a blob at the root of the tree pointed at by the tip of [`STATE_REF`],
+/// and saving a key commits a new tree built from the previous tip plus the
+/// updated blob.
+pub struct GitStateStore {
+    repo: Repository,
+}
+
+impl GitStateStore {
+    /// Opens the state repository at `repo_path`, initializing it if it doesn't
+    /// exist yet. The `refs/patch-hub/state` ref is created by the first `save`.
+    pub fn open_or_init(repo_path: &Path) -> Result<GitStateStore, GitStateStoreError> {
+        let repo = match Repository::open(repo_path) {
+            Ok(repo) => repo,
+            Err(_) => Repository::init(repo_path)?,
+        };
+
+        Ok(GitStateStore { repo })
+    }
+
+    /// Serializes `value` to JSON and commits it onto `refs/patch-hub/state` under `key`.
+    pub fn save<T: Serialize>(&self, key: &str, value: &T) -> Result<(), GitStateStoreError> {
+        let contents = serde_json::to_vec_pretty(value)?;
+        let blob_id = self.repo.blob(&contents)?;
+
+        let mut tree_builder = match self.repo.refname_to_id(STATE_REF) {
+            Ok(tip_oid) => {
+                let tip_commit = self.repo.find_commit(tip_oid)?;
+                self.repo.treebuilder(Some(&tip_commit.tree()?))?
+            }
+            Err(_) => self.repo.treebuilder(None)?,
+        };
+        tree_builder.insert(key, blob_id, 0o100644)?;
+        let tree_id = tree_builder.write()?;
+        let tree = self.repo.find_tree(tree_id)?;
+
+        let signature = Signature::now("patch-hub", "patch-hub@localhost")?;
+        let parents = match self.repo.refname_to_id(STATE_REF) {
+            Ok(tip_oid) => vec![self.repo.find_commit(tip_oid)?],
+            Err(_) => Vec::new(),
+        };
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+        let commit_id = self.repo.commit(
+            Some(STATE_REF),
+            &signature,
+            &signature,
+            &format!("update {key}"),
+            &tree,
+            &parent_refs,
+        )?;
+        let _ = commit_id;
+
+        Ok(())
+    }
+
+    /// Reconstructs the latest value saved under `key` by walking `refs/patch-hub/state`
+    /// and reading `key` out of the tip tree. Returns `Ok(None)` if the ref or the key
+    /// doesn't exist yet.
+    pub fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, GitStateStoreError> {
+        let tip_oid = match self.repo.refname_to_id(STATE_REF) {
+            Ok(oid) => oid,
+            Err(_) => return Ok(None),
+        };
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(tip_oid)?;
+
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            let tree = commit.tree()?;
+
+            if let Ok(entry) = tree.get_path(Path::new(key)) {
+                let blob = entry.to_object(&self.repo)?.peel_to_blob()?;
+                let value = serde_json::from_slice(blob.content())?;
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+}
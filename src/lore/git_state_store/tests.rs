@@ -0,0 +1,65 @@
+use super::*;
+use std::fs;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn tmp_repo_path() -> std::path::PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("patch-hub-git-state-store-test-{}-{n}", process::id()))
+}
+
+#[test]
+fn load_on_empty_store_returns_none() {
+    let repo_path = tmp_repo_path();
+    let store = GitStateStore::open_or_init(&repo_path).unwrap();
+
+    let loaded: Option<Vec<String>> = store.load("bookmarked_patchsets").unwrap();
+
+    assert!(loaded.is_none());
+    fs::remove_dir_all(&repo_path).unwrap();
+}
+
+#[test]
+fn save_then_load_round_trips_the_value() {
+    let repo_path = tmp_repo_path();
+    let store = GitStateStore::open_or_init(&repo_path).unwrap();
+
+    let bookmarks = vec!["a@lore".to_owned(), "b@lore".to_owned()];
+    store.save("bookmarked_patchsets", &bookmarks).unwrap();
+
+    let loaded: Option<Vec<String>> = store.load("bookmarked_patchsets").unwrap();
+
+    assert_eq!(loaded, Some(bookmarks));
+    fs::remove_dir_all(&repo_path).unwrap();
+}
+
+#[test]
+fn later_save_overwrites_the_previous_value_for_the_same_key() {
+    let repo_path = tmp_repo_path();
+    let store = GitStateStore::open_or_init(&repo_path).unwrap();
+
+    store.save("bookmarked_patchsets", &vec!["a@lore".to_owned()]).unwrap();
+    store.save("bookmarked_patchsets", &vec!["b@lore".to_owned()]).unwrap();
+
+    let loaded: Option<Vec<String>> = store.load("bookmarked_patchsets").unwrap();
+
+    assert_eq!(loaded, Some(vec!["b@lore".to_owned()]));
+    fs::remove_dir_all(&repo_path).unwrap();
+}
+
+#[test]
+fn different_keys_dont_clobber_each_other() {
+    let repo_path = tmp_repo_path();
+    let store = GitStateStore::open_or_init(&repo_path).unwrap();
+
+    store.save("bookmarked_patchsets", &vec!["a@lore".to_owned()]).unwrap();
+    store.save("reviewed_patchsets", &vec!["b@lore".to_owned()]).unwrap();
+
+    let bookmarked: Option<Vec<String>> = store.load("bookmarked_patchsets").unwrap();
+    let reviewed: Option<Vec<String>> = store.load("reviewed_patchsets").unwrap();
+
+    assert_eq!(bookmarked, Some(vec!["a@lore".to_owned()]));
+    assert_eq!(reviewed, Some(vec!["b@lore".to_owned()]));
+    fs::remove_dir_all(&repo_path).unwrap();
+}
@@ -1,9 +1,11 @@
+use crate::lore::git_state_store::GitStateStore;
 use crate::lore::lore_api_client::{
     AvailableListsRequest, ClientError, PatchFeedRequest, PatchHTMLRequest,
 };
 use crate::lore::mailing_list::MailingList;
 use crate::lore::patch::{Patch, PatchFeed, PatchRegex};
 use derive_getters::Getters;
+use rayon::prelude::*;
 use regex::Regex;
 use serde_xml_rs::from_str;
 use std::collections::{HashMap, HashSet};
@@ -23,6 +25,14 @@ mod tests;
 
 const LORE_PAGE_SIZE: usize = 200;
 
+/// Selects where the `save_*`/`load_*` functions below persist their state: the
+/// default flat-JSON files, or an opt-in [`GitStateStore`] that keeps full history
+/// on a private ref and can be pushed/pulled to sync across machines.
+pub enum PersistenceBackend<'a> {
+    Json,
+    Git(&'a GitStateStore),
+}
+
 #[derive(Getters)]
 pub struct LoreSession {
     representative_patches_ids: Vec<String>,
@@ -40,6 +50,8 @@ pub struct LoreSession {
 pub enum LoreSessionError {
     #[error(transparent)]
     FromLoreAPIClient(#[from] ClientError),
+    #[error(transparent)]
+    FromSmtpBackend(#[from] crate::lore::smtp_backend::SmtpReplyError),
 }
 
 impl LoreSession {
@@ -80,9 +92,20 @@ impl LoreSession {
     fn process_patches(&mut self, patch_feed: PatchFeed) -> Vec<String> {
         let mut processed_patches_ids: Vec<String> = Vec::new();
 
-        for mut patch in patch_feed.patches().clone() {
-            patch.update_patch_metadata(&self.patch_regex);
-
+        // `update_patch_metadata` is pure per-patch work, so it can run across the feed
+        // entries in parallel; only the `processed_patches_map` insertion below needs to
+        // stay single-threaded, to preserve insertion order semantics.
+        let updated_patches: Vec<Patch> = patch_feed
+            .patches()
+            .clone()
+            .into_par_iter()
+            .map(|mut patch| {
+                patch.update_patch_metadata(&self.patch_regex);
+                patch
+            })
+            .collect();
+
+        for patch in updated_patches {
             if !self
                 .processed_patches_map
                 .contains_key(&patch.message_id().href)
@@ -180,6 +203,16 @@ pub fn download_patchset(output_dir: &str, patch: &Patch) -> io::Result<String>
     Ok(filepath)
 }
 
+/// Downloads `patches` concurrently across a rayon thread pool, one `b4 am` invocation
+/// per patch, and collects each patch's `io::Result<String>` in the same order as
+/// `patches` without interleaving the underlying `b4` processes' output.
+pub fn download_patchsets(output_dir: &str, patches: &[&Patch]) -> Vec<io::Result<String>> {
+    patches
+        .par_iter()
+        .map(|patch| download_patchset(output_dir, patch))
+        .collect()
+}
+
 fn extract_mbox_name_from_message_id(message_id: &str) -> String {
     let mut mbox_name: String = message_id
         .replace(r#"http://lore.kernel.org/"#, "")
@@ -276,24 +309,43 @@ fn extract_patches(mbox_path: &Path, patches: &mut Vec<String>) {
 pub fn save_bookmarked_patchsets(
     bookmarked_patchsets: &Vec<Patch>,
     filepath: &str,
+    backend: &PersistenceBackend,
 ) -> io::Result<()> {
-    if let Some(parent) = Path::new(filepath).parent() {
-        fs::create_dir_all(parent)?;
-    }
+    match backend {
+        PersistenceBackend::Git(store) => Ok(store.save("bookmarked_patchsets", bookmarked_patchsets)?),
+        PersistenceBackend::Json => {
+            if let Some(parent) = Path::new(filepath).parent() {
+                fs::create_dir_all(parent)?;
+            }
 
-    let tmp_filename = format!("{}.tmp", filepath);
-    {
-        let tmp_file = File::create(&tmp_filename)?;
-        serde_json::to_writer(tmp_file, &bookmarked_patchsets)?;
+            let tmp_filename = format!("{}.tmp", filepath);
+            {
+                let tmp_file = File::create(&tmp_filename)?;
+                serde_json::to_writer(tmp_file, &bookmarked_patchsets)?;
+            }
+            fs::rename(tmp_filename, filepath)?;
+            Ok(())
+        }
     }
-    fs::rename(tmp_filename, filepath)?;
-    Ok(())
 }
 
-pub fn load_bookmarked_patchsets(filepath: &str) -> io::Result<Vec<Patch>> {
-    let bookmarked_patchsets_file = File::open(filepath)?;
-    let bookmarked_patchesets = serde_json::from_reader(bookmarked_patchsets_file)?;
-    Ok(bookmarked_patchesets)
+pub fn load_bookmarked_patchsets(
+    filepath: &str,
+    backend: &PersistenceBackend,
+) -> io::Result<Vec<Patch>> {
+    match backend {
+        PersistenceBackend::Git(store) => store.load("bookmarked_patchsets")?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no bookmarked patchsets saved in the git state store yet",
+            )
+        }),
+        PersistenceBackend::Json => {
+            let bookmarked_patchsets_file = File::open(filepath)?;
+            let bookmarked_patchesets = serde_json::from_reader(bookmarked_patchsets_file)?;
+            Ok(bookmarked_patchesets)
+        }
+    }
 }
 
 pub fn fetch_available_lists<T>(lore_api_client: &T) -> Result<Vec<MailingList>, LoreSessionError>
@@ -361,24 +413,54 @@ fn process_available_lists(available_lists_str: String) -> Vec<MailingList> {
     available_lists
 }
 
-pub fn save_available_lists(available_lists: &Vec<MailingList>, filepath: &str) -> io::Result<()> {
-    if let Some(parent) = Path::new(filepath).parent() {
-        fs::create_dir_all(parent)?;
+pub fn save_available_lists(
+    available_lists: &Vec<MailingList>,
+    filepath: &str,
+    backend: &PersistenceBackend,
+) -> io::Result<()> {
+    match backend {
+        PersistenceBackend::Git(store) => Ok(store.save("available_lists", available_lists)?),
+        PersistenceBackend::Json => {
+            if let Some(parent) = Path::new(filepath).parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let tmp_filename = format!("{}.tmp", filepath);
+            {
+                let tmp_file = File::create(&tmp_filename)?;
+                serde_json::to_writer(tmp_file, &available_lists)?;
+            }
+            fs::rename(tmp_filename, filepath)?;
+            Ok(())
+        }
     }
+}
 
-    let tmp_filename = format!("{}.tmp", filepath);
-    {
-        let tmp_file = File::create(&tmp_filename)?;
-        serde_json::to_writer(tmp_file, &available_lists)?;
+pub fn load_available_lists(
+    filepath: &str,
+    backend: &PersistenceBackend,
+) -> io::Result<Vec<MailingList>> {
+    match backend {
+        PersistenceBackend::Git(store) => store.load("available_lists")?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no available lists saved in the git state store yet",
+            )
+        }),
+        PersistenceBackend::Json => {
+            let available_lists_file = File::open(filepath)?;
+            let available_lists = serde_json::from_reader(available_lists_file)?;
+            Ok(available_lists)
+        }
     }
-    fs::rename(tmp_filename, filepath)?;
-    Ok(())
 }
 
-pub fn load_available_lists(filepath: &str) -> io::Result<Vec<MailingList>> {
-    let available_lists_file = File::open(filepath)?;
-    let available_lists = serde_json::from_reader(available_lists_file)?;
-    Ok(available_lists)
+/// User-configurable knobs for how `generate_patch_reply_template` shapes a reply.
+#[derive(Default, Clone)]
+pub struct ReplyTemplateConfig {
+    pub subject_prefixes_to_strip: Vec<Regex>,
+    pub preamble: Option<String>,
+    pub suffix: Option<String>,
 }
 
 pub fn prepare_reply_patchset_with_reviewed_by<T>(
@@ -389,6 +471,7 @@ pub fn prepare_reply_patchset_with_reviewed_by<T>(
     patches_to_reply: &[bool],
     git_signature: &str,
     git_send_email_options: &str,
+    reply_template_config: &ReplyTemplateConfig,
 ) -> Result<Vec<Command>, LoreSessionError>
 where
     T: PatchHTMLRequest,
@@ -411,7 +494,7 @@ where
             .as_str();
 
         let reply_path = tmp_dir.join(format!("{message_id}-reply.mbx"));
-        let mut reply = generate_patch_reply_template(patch);
+        let mut reply = generate_patch_reply_template(patch, reply_template_config);
         reply.push_str(&format!("\nReviewed-by: {git_signature}\n"));
         fs::write(&reply_path, &reply).unwrap();
 
@@ -426,7 +509,28 @@ where
     Ok(git_reply_commands)
 }
 
-fn generate_patch_reply_template(patch_contents: &str) -> String {
+pub(crate) fn strip_subject_prefixes(subject: &str, prefixes_to_strip: &[Regex]) -> String {
+    let mut stripped = subject.trim_start();
+
+    loop {
+        let leading_match = prefixes_to_strip
+            .iter()
+            .find_map(|pattern| pattern.find(stripped))
+            .filter(|m| m.start() == 0);
+
+        match leading_match {
+            Some(m) => stripped = stripped[m.end()..].trim_start(),
+            None => break,
+        }
+    }
+
+    stripped.to_owned()
+}
+
+pub(crate) fn generate_patch_reply_template(
+    patch_contents: &str,
+    reply_template_config: &ReplyTemplateConfig,
+) -> String {
     let mut reply_template = String::new();
     let mut patch_lines_iterator = patch_contents.lines();
 
@@ -434,8 +538,10 @@ fn generate_patch_reply_template(patch_contents: &str) -> String {
     for line in patch_lines_iterator.by_ref() {
         let mut line_to_push = String::new();
 
-        if line.starts_with("Subject: ") {
-            line_to_push = line.replace("Subject: ", "Subject: Re: ") + "\n";
+        if let Some(subject) = line.strip_prefix("Subject: ") {
+            let stripped_subject =
+                strip_subject_prefixes(subject, &reply_template_config.subject_prefixes_to_strip);
+            line_to_push = format!("Subject: Re: {stripped_subject}\n");
         } else if line.starts_with("From: ")
             || line.starts_with("Date: ")
             || line.starts_with("Message-Id: ")
@@ -451,11 +557,25 @@ fn generate_patch_reply_template(patch_contents: &str) -> String {
         reply_template.push_str(&line_to_push);
     }
 
+    if let Some(preamble) = &reply_template_config.preamble {
+        reply_template.push_str(preamble);
+        if !preamble.ends_with('\n') {
+            reply_template.push('\n');
+        }
+    }
+
     // After processing headers, just quote-reply remaining lines
     for line in patch_lines_iterator {
         reply_template.push_str(&format!("> {}\n", line));
     }
 
+    if let Some(suffix) = &reply_template_config.suffix {
+        reply_template.push_str(suffix);
+        if !suffix.ends_with('\n') {
+            reply_template.push('\n');
+        }
+    }
+
     reply_template
 }
 
@@ -520,22 +640,41 @@ pub fn get_git_signature(git_repo_path: &str) -> (String, String) {
 pub fn save_reviewed_patchsets(
     reviewed_patchsets: &HashMap<String, HashSet<usize>>,
     filepath: &str,
+    backend: &PersistenceBackend,
 ) -> io::Result<()> {
-    if let Some(parent) = Path::new(filepath).parent() {
-        fs::create_dir_all(parent)?;
-    }
+    match backend {
+        PersistenceBackend::Git(store) => Ok(store.save("reviewed_patchsets", reviewed_patchsets)?),
+        PersistenceBackend::Json => {
+            if let Some(parent) = Path::new(filepath).parent() {
+                fs::create_dir_all(parent)?;
+            }
 
-    let tmp_filename = format!("{}.tmp", filepath);
-    {
-        let tmp_file = File::create(&tmp_filename)?;
-        serde_json::to_writer(tmp_file, &reviewed_patchsets)?;
+            let tmp_filename = format!("{}.tmp", filepath);
+            {
+                let tmp_file = File::create(&tmp_filename)?;
+                serde_json::to_writer(tmp_file, &reviewed_patchsets)?;
+            }
+            fs::rename(tmp_filename, filepath)?;
+            Ok(())
+        }
     }
-    fs::rename(tmp_filename, filepath)?;
-    Ok(())
 }
 
-pub fn load_reviewed_patchsets(filepath: &str) -> io::Result<HashMap<String, HashSet<usize>>> {
-    let reviewed_patchsets_file = File::open(filepath)?;
-    let reviewed_patchsets = serde_json::from_reader(reviewed_patchsets_file)?;
-    Ok(reviewed_patchsets)
+pub fn load_reviewed_patchsets(
+    filepath: &str,
+    backend: &PersistenceBackend,
+) -> io::Result<HashMap<String, HashSet<usize>>> {
+    match backend {
+        PersistenceBackend::Git(store) => store.load("reviewed_patchsets")?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no reviewed patchsets saved in the git state store yet",
+            )
+        }),
+        PersistenceBackend::Json => {
+            let reviewed_patchsets_file = File::open(filepath)?;
+            let reviewed_patchsets = serde_json::from_reader(reviewed_patchsets_file)?;
+            Ok(reviewed_patchsets)
+        }
+    }
 }
@@ -0,0 +1,71 @@
+use super::*;
+
+fn patch_config() -> ReplyTemplateConfig {
+    ReplyTemplateConfig {
+        subject_prefixes_to_strip: vec![Regex::new(r"^\[PATCH[^\]]*\]\s*").unwrap()],
+        preamble: None,
+        suffix: None,
+    }
+}
+
+#[test]
+fn strip_subject_prefixes_removes_a_single_bracketed_tag() {
+    let prefixes = vec![Regex::new(r"^\[PATCH[^\]]*\]\s*").unwrap()];
+
+    assert_eq!(
+        strip_subject_prefixes("[PATCH v2 1/3] actual subject", &prefixes),
+        "actual subject"
+    );
+}
+
+#[test]
+fn strip_subject_prefixes_removes_repeated_tags() {
+    let prefixes = vec![
+        Regex::new(r"^\[PATCH[^\]]*\]\s*").unwrap(),
+        Regex::new(r"^\[RFC\]\s*").unwrap(),
+    ];
+
+    assert_eq!(
+        strip_subject_prefixes("[RFC] [PATCH v2] actual subject", &prefixes),
+        "actual subject"
+    );
+}
+
+#[test]
+fn strip_subject_prefixes_leaves_subject_untouched_when_nothing_matches() {
+    let prefixes = vec![Regex::new(r"^\[PATCH[^\]]*\]\s*").unwrap()];
+
+    assert_eq!(
+        strip_subject_prefixes("actual subject", &prefixes),
+        "actual subject"
+    );
+}
+
+#[test]
+fn generate_patch_reply_template_strips_configured_subject_prefixes() {
+    let patch = "From: jane@example.com\nSubject: [PATCH v2 1/3] actual subject\nDate: today\nMessage-Id: <id>\n\nbody line\n";
+
+    let reply = generate_patch_reply_template(patch, &patch_config());
+
+    assert!(reply.starts_with("Subject: Re: actual subject\n"));
+    assert!(reply.contains("> body line\n"));
+}
+
+#[test]
+fn generate_patch_reply_template_injects_preamble_and_suffix() {
+    let patch = "Subject: fix the thing\n\nbody line\n";
+    let config = ReplyTemplateConfig {
+        subject_prefixes_to_strip: Vec::new(),
+        preamble: Some("Looks good overall.".to_owned()),
+        suffix: Some("-- \nSent from patch-hub".to_owned()),
+    };
+
+    let reply = generate_patch_reply_template(patch, &config);
+
+    let preamble_index = reply.find("Looks good overall.").unwrap();
+    let quote_index = reply.find("> body line").unwrap();
+    let suffix_index = reply.find("Sent from patch-hub").unwrap();
+
+    assert!(preamble_index < quote_index);
+    assert!(quote_index < suffix_index);
+}
@@ -0,0 +1,251 @@
+//! Dry-run application of a downloaded patchset against a local tree.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+#[cfg(test)]
+mod tests;
+
+/// A single `@@ -old_range +new_range @@` hunk from a patch's diff for one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub file: String,
+    pub old_range: (usize, usize),
+    pub new_range: (usize, usize),
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub conflicted: bool,
+}
+
+/// The result of dry-run applying a single patch from the series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchApplyReport {
+    pub applies_cleanly: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Applies each patch's diff (as produced by `split_cover`) cumulatively in a
+/// scratch worktree checked out from `repo_path`'s current `HEAD`, and reports,
+/// per patch, whether it applied cleanly and the hunks it touches.
+///
+/// Each patch is first checked with `git apply --3way --check` against whatever
+/// the scratch worktree looks like after the previous patches in the series; if
+/// that succeeds, the patch is actually applied (for real, not `--check`) into
+/// the scratch worktree so the next patch in the series is checked against the
+/// right base, rather than every patch being re-checked against the original,
+/// unmodified tree. Patches that fail the check are marked `conflicted` without
+/// being applied, and the scratch worktree is discarded once the series has been
+/// fully previewed.
+pub fn preview_patchset_application(
+    repo_path: &Path,
+    diffs: &[&str],
+) -> io::Result<Vec<PatchApplyReport>> {
+    let scratch_dir = add_scratch_worktree(repo_path)?;
+    let result = preview_in_scratch_worktree(&scratch_dir, diffs);
+    remove_scratch_worktree(repo_path, &scratch_dir);
+    result
+}
+
+fn preview_in_scratch_worktree(
+    scratch_dir: &Path,
+    diffs: &[&str],
+) -> io::Result<Vec<PatchApplyReport>> {
+    let mut reports = Vec::with_capacity(diffs.len());
+
+    for diff in diffs {
+        let applies_cleanly = apply_diff(scratch_dir, diff, true)?;
+        let mut hunks = parse_unified_diff_hunks(diff);
+
+        if applies_cleanly {
+            apply_diff(scratch_dir, diff, false)?;
+        } else {
+            for hunk in &mut hunks {
+                hunk.conflicted = true;
+            }
+        }
+
+        reports.push(PatchApplyReport {
+            applies_cleanly,
+            hunks,
+        });
+    }
+
+    Ok(reports)
+}
+
+fn add_scratch_worktree(repo_path: &Path) -> io::Result<PathBuf> {
+    let scratch_dir = std::env::temp_dir().join(format!(
+        "patch-hub-apply-preview-{}-{}",
+        std::process::id(),
+        unique_suffix()
+    ));
+
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("worktree")
+        .arg("add")
+        .arg("--detach")
+        .arg(&scratch_dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    if !status.success() {
+        return Err(io::Error::other(
+            "failed to create scratch worktree for patchset apply preview",
+        ));
+    }
+
+    Ok(scratch_dir)
+}
+
+fn remove_scratch_worktree(repo_path: &Path, scratch_dir: &Path) {
+    let _ = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("worktree")
+        .arg("remove")
+        .arg("--force")
+        .arg(scratch_dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+fn unique_suffix() -> u128 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default()
+}
+
+/// Dry-run checks (`check_only`) or actually applies `diff` against `worktree_dir`
+/// with a three-way merge, feeding the diff over stdin.
+fn apply_diff(worktree_dir: &Path, diff: &str, check_only: bool) -> io::Result<bool> {
+    use std::io::Write;
+
+    let mut command = Command::new("git");
+    command.arg("-C").arg(worktree_dir).arg("apply").arg("--3way");
+    if check_only {
+        command.arg("--check");
+    }
+    command.arg("-");
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(diff.as_bytes())?;
+
+    Ok(child.wait()?.success())
+}
+
+/// Parses a unified diff into per-file hunks without invoking git, used to report
+/// the hunk ranges of every patch, whether or not it applied cleanly. It is not a
+/// fallback for a missing `git`: without it, `preview_patchset_application` returns
+/// an error.
+fn parse_unified_diff_hunks(diff: &str) -> Vec<Hunk> {
+    static RE_FILE_HEADER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\+\+\+ [ab]?/?(.*)$").unwrap());
+    static RE_HUNK_HEADER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").unwrap());
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut current_file = String::new();
+    let mut in_hunk = false;
+    let mut added_lines = 0;
+    let mut removed_lines = 0;
+    let mut pending_ranges: Option<((usize, usize), (usize, usize))> = None;
+
+    for line in diff.lines() {
+        if let Some(capture) = RE_FILE_HEADER.captures(line) {
+            flush_hunk(
+                &mut hunks,
+                &current_file,
+                &mut in_hunk,
+                &mut pending_ranges,
+                &mut added_lines,
+                &mut removed_lines,
+            );
+            current_file = capture.get(1).unwrap().as_str().to_owned();
+            continue;
+        }
+
+        if let Some(capture) = RE_HUNK_HEADER.captures(line) {
+            flush_hunk(
+                &mut hunks,
+                &current_file,
+                &mut in_hunk,
+                &mut pending_ranges,
+                &mut added_lines,
+                &mut removed_lines,
+            );
+
+            let old_start: usize = capture[1].parse().unwrap();
+            let old_len: usize = capture.get(2).map_or(1, |m| m.as_str().parse().unwrap());
+            let new_start: usize = capture[3].parse().unwrap();
+            let new_len: usize = capture.get(4).map_or(1, |m| m.as_str().parse().unwrap());
+
+            pending_ranges = Some(((old_start, old_len), (new_start, new_len)));
+            in_hunk = true;
+            continue;
+        }
+
+        if in_hunk {
+            if line.starts_with('+') && !line.starts_with("+++") {
+                added_lines += 1;
+            } else if line.starts_with('-') && !line.starts_with("---") {
+                removed_lines += 1;
+            }
+        }
+    }
+
+    flush_hunk(
+        &mut hunks,
+        &current_file,
+        &mut in_hunk,
+        &mut pending_ranges,
+        &mut added_lines,
+        &mut removed_lines,
+    );
+
+    hunks
+}
+
+#[allow(clippy::too_many_arguments)]
+fn flush_hunk(
+    hunks: &mut Vec<Hunk>,
+    file: &str,
+    in_hunk: &mut bool,
+    pending_ranges: &mut Option<((usize, usize), (usize, usize))>,
+    added_lines: &mut usize,
+    removed_lines: &mut usize,
+) {
+    if let Some((old_range, new_range)) = pending_ranges.take() {
+        hunks.push(Hunk {
+            file: file.to_owned(),
+            old_range,
+            new_range,
+            added_lines: *added_lines,
+            removed_lines: *removed_lines,
+            conflicted: false,
+        });
+    }
+
+    *in_hunk = false;
+    *added_lines = 0;
+    *removed_lines = 0;
+}
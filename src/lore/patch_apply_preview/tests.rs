@@ -0,0 +1,137 @@
+use super::*;
+use std::fs;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn parses_added_and_removed_line_counts_per_hunk() {
+    let diff = "\
+diff --git a/foo.txt b/foo.txt
+index 0000000..1111111 100644
+--- a/foo.txt
++++ b/foo.txt
+@@ -1,2 +1,3 @@
+ unchanged
+-removed line
++added line
++another added line
+";
+
+    let hunks = parse_unified_diff_hunks(diff);
+
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].file, "foo.txt");
+    assert_eq!(hunks[0].old_range, (1, 2));
+    assert_eq!(hunks[0].new_range, (1, 3));
+    assert_eq!(hunks[0].added_lines, 2);
+    assert_eq!(hunks[0].removed_lines, 1);
+    assert!(!hunks[0].conflicted);
+}
+
+#[test]
+fn parses_hunks_across_multiple_files() {
+    let diff = "\
+diff --git a/a.txt b/a.txt
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-old a
++new a
+diff --git a/b.txt b/b.txt
+--- a/b.txt
++++ b/b.txt
+@@ -5,1 +5,2 @@
++new b
+ unchanged
+";
+
+    let hunks = parse_unified_diff_hunks(diff);
+
+    assert_eq!(hunks.len(), 2);
+    assert_eq!(hunks[0].file, "a.txt");
+    assert_eq!(hunks[1].file, "b.txt");
+}
+
+fn tmp_repo_path() -> std::path::PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "patch-hub-apply-preview-test-{}-{n}",
+        std::process::id()
+    ))
+}
+
+fn run_git(repo_path: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+fn init_repo_with_file(contents: &str) -> std::path::PathBuf {
+    let repo_path = tmp_repo_path();
+    fs::create_dir_all(&repo_path).unwrap();
+    run_git(&repo_path, &["init", "-q"]);
+    run_git(&repo_path, &["config", "user.email", "test@example.com"]);
+    run_git(&repo_path, &["config", "user.name", "Test"]);
+    fs::write(repo_path.join("file.txt"), contents).unwrap();
+    run_git(&repo_path, &["add", "."]);
+    run_git(&repo_path, &["commit", "-q", "-m", "initial"]);
+    repo_path
+}
+
+#[test]
+fn second_patch_applies_cleanly_on_top_of_the_first() {
+    let repo_path = init_repo_with_file("one\ntwo\nthree\n");
+
+    let first_diff = "\
+--- a/file.txt
++++ b/file.txt
+@@ -1,3 +1,3 @@
+-one
++ONE
+ two
+ three
+";
+    let second_diff = "\
+--- a/file.txt
++++ b/file.txt
+@@ -1,3 +1,3 @@
+ ONE
+-two
++TWO
+ three
+";
+
+    let reports = preview_patchset_application(&repo_path, &[first_diff, second_diff]).unwrap();
+
+    assert!(reports[0].applies_cleanly);
+    assert!(reports[1].applies_cleanly);
+
+    fs::remove_dir_all(&repo_path).unwrap();
+}
+
+#[test]
+fn patch_against_unrelated_content_is_marked_conflicted() {
+    let repo_path = init_repo_with_file("one\ntwo\nthree\n");
+
+    let unrelated_diff = "\
+--- a/file.txt
++++ b/file.txt
+@@ -1,3 +1,3 @@
+-nonexistent
++replacement
+ two
+ three
+";
+
+    let reports = preview_patchset_application(&repo_path, &[unrelated_diff]).unwrap();
+
+    assert!(!reports[0].applies_cleanly);
+    assert!(reports[0].hunks.iter().all(|hunk| hunk.conflicted));
+
+    fs::remove_dir_all(&repo_path).unwrap();
+}
@@ -0,0 +1,182 @@
+//! Background watcher that polls subscribed mailing lists for newly-arrived
+//! patch series and fires a desktop notification per series.
+
+use crate::lore::lore_api_client::PatchFeedRequest;
+use crate::lore::lore_session::{strip_subject_prefixes, LoreSession};
+use notify_rust::Notification;
+use regex::Regex;
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests;
+
+/// A window of local hours (`start_hour..end_hour`, wrapping past midnight) during
+/// which the watcher still polls but suppresses notifications.
+#[derive(Clone, Copy)]
+pub struct QuietHours {
+    pub start_hour: u8,
+    pub end_hour: u8,
+}
+
+impl QuietHours {
+    fn contains(&self, hour: u8) -> bool {
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// Configuration for a [`PatchWatcher`].
+pub struct PatchWatcherConfig {
+    pub target_lists: Vec<String>,
+    pub poll_interval: Duration,
+    pub quiet_hours: Option<QuietHours>,
+    pub last_seen_ids_path: PathBuf,
+    /// Same knob as `ReplyTemplateConfig::subject_prefixes_to_strip`, applied to
+    /// notification titles so a user's configured tags (e.g. `[PATCH ...]`) don't
+    /// show up twice: once when replying, once in the notification.
+    pub subject_prefixes_to_strip: Vec<Regex>,
+}
+
+/// Polls `config.target_lists` every `config.poll_interval` and notifies about any
+/// series whose id isn't already in the persisted last-seen set from earlier polls.
+pub struct PatchWatcher {
+    config: PatchWatcherConfig,
+    last_seen_ids: HashSet<String>,
+}
+
+/// A running background poll loop started by [`PatchWatcher::spawn`].
+pub struct PatchWatcherHandle {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    join_handle: thread::JoinHandle<()>,
+}
+
+impl PatchWatcherHandle {
+    /// Signals the poll loop to stop and waits for its current iteration to finish.
+    /// Wakes the loop immediately even if it's in the middle of `poll_interval`, so
+    /// this returns promptly regardless of how long that interval is configured.
+    pub fn stop(self) {
+        let (lock, condvar) = &*self.stop;
+        *lock.lock().unwrap() = true;
+        condvar.notify_one();
+        let _ = self.join_handle.join();
+    }
+}
+
+impl PatchWatcher {
+    pub fn new(config: PatchWatcherConfig) -> io::Result<PatchWatcher> {
+        let last_seen_ids = load_last_seen_ids(&config.last_seen_ids_path).unwrap_or_default();
+
+        Ok(PatchWatcher {
+            config,
+            last_seen_ids,
+        })
+    }
+
+    /// Spawns a background thread that calls `poll_once` every `config.poll_interval`
+    /// until the returned [`PatchWatcherHandle`] is stopped. `current_hour` is called
+    /// once per iteration to get the local hour for the quiet-hours check.
+    pub fn spawn<T, F>(mut self, lore_api_client: T, current_hour: F) -> PatchWatcherHandle
+    where
+        T: PatchFeedRequest + Send + 'static,
+        F: Fn() -> u8 + Send + 'static,
+    {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let stop_in_thread = Arc::clone(&stop);
+
+        let join_handle = thread::spawn(move || {
+            let (lock, condvar) = &*stop_in_thread;
+            let mut stopped = lock.lock().unwrap();
+
+            while !*stopped {
+                if let Err(err) = self.poll_once(&lore_api_client, current_hour()) {
+                    eprintln!("patch watcher poll failed: {err}");
+                }
+
+                stopped = condvar
+                    .wait_timeout_while(stopped, self.config.poll_interval, |stopped| !*stopped)
+                    .unwrap()
+                    .0;
+            }
+        });
+
+        PatchWatcherHandle { stop, join_handle }
+    }
+
+    /// Runs one poll of every subscribed list, notifying about newly-arrived series
+    /// and persisting the updated last-seen id set.
+    pub fn poll_once<T: PatchFeedRequest>(
+        &mut self,
+        lore_api_client: &T,
+        current_hour: u8,
+    ) -> io::Result<()> {
+        let in_quiet_hours = self
+            .config
+            .quiet_hours
+            .is_some_and(|quiet_hours| quiet_hours.contains(current_hour));
+
+        for target_list in self.config.target_lists.clone() {
+            let mut session = LoreSession::new(target_list.clone());
+            if session.process_n_representative_patches(lore_api_client, 1).is_err() {
+                continue;
+            }
+
+            for message_id in session.representative_patches_ids() {
+                if self.last_seen_ids.contains(message_id) {
+                    continue;
+                }
+
+                self.last_seen_ids.insert(message_id.clone());
+
+                if let Some(patch) = session.get_processed_patch(message_id) {
+                    if !in_quiet_hours {
+                        let title = strip_subject_prefixes(
+                            patch.title(),
+                            &self.config.subject_prefixes_to_strip,
+                        );
+                        notify_new_patch(&target_list, &title, patch.author());
+                    }
+                }
+            }
+        }
+
+        save_last_seen_ids(&self.config.last_seen_ids_path, &self.last_seen_ids)
+    }
+}
+
+fn notify_new_patch(target_list: &str, title: &str, author: &str) {
+    // `notify-rust` already dispatches to `osascript` on macOS and `notify-send`
+    // (via its D-Bus/XDG backend) on Linux, so no per-OS branching is needed here.
+    let _ = Notification::new()
+        .summary(title)
+        .body(&format!("{author} on {target_list}"))
+        .show();
+}
+
+fn load_last_seen_ids(path: &Path) -> io::Result<HashSet<String>> {
+    let file = std::fs::File::open(path)?;
+    let ids = serde_json::from_reader(file)?;
+    Ok(ids)
+}
+
+fn save_last_seen_ids(path: &Path, ids: &HashSet<String>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    {
+        let tmp_file = std::fs::File::create(&tmp_path)?;
+        serde_json::to_writer(tmp_file, ids)?;
+    }
+    std::fs::rename(tmp_path, path)?;
+
+    Ok(())
+}
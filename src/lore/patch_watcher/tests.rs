@@ -0,0 +1,52 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn quiet_hours_contains_within_a_same_day_window() {
+    let quiet_hours = QuietHours { start_hour: 22, end_hour: 23 };
+
+    assert!(quiet_hours.contains(22));
+    assert!(!quiet_hours.contains(23));
+    assert!(!quiet_hours.contains(10));
+}
+
+#[test]
+fn quiet_hours_contains_across_midnight() {
+    let quiet_hours = QuietHours { start_hour: 22, end_hour: 6 };
+
+    assert!(quiet_hours.contains(23));
+    assert!(quiet_hours.contains(0));
+    assert!(quiet_hours.contains(5));
+    assert!(!quiet_hours.contains(6));
+    assert!(!quiet_hours.contains(12));
+}
+
+fn tmp_path() -> std::path::PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "patch-hub-watcher-test-{}-{n}.json",
+        std::process::id()
+    ))
+}
+
+#[test]
+fn last_seen_ids_round_trip_through_save_and_load() {
+    let path = tmp_path();
+    let mut ids = HashSet::new();
+    ids.insert("a@lore".to_owned());
+    ids.insert("b@lore".to_owned());
+
+    save_last_seen_ids(&path, &ids).unwrap();
+    let loaded = load_last_seen_ids(&path).unwrap();
+
+    assert_eq!(loaded, ids);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn load_last_seen_ids_errors_when_the_file_is_missing() {
+    let path = tmp_path();
+
+    assert!(load_last_seen_ids(&path).is_err());
+}
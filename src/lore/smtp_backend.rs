@@ -0,0 +1,199 @@
+//! Alternative backend for `prepare_reply_patchset_with_reviewed_by` that sends the
+//! generated Reviewed-by reply over SMTP (via `lettre`) instead of shelling out to
+//! `git send-email`.
+
+use crate::lore::lore_api_client::PatchHTMLRequest;
+use crate::lore::lore_session::{generate_patch_reply_template, ReplyTemplateConfig};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{Message, SmtpTransport, Transport};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Error, Debug)]
+pub enum SmtpReplyError {
+    #[error("couldn't find a `{0}:` header in the patch")]
+    MissingHeader(&'static str),
+    #[error(transparent)]
+    FromLettreAddress(#[from] lettre::address::AddressError),
+    #[error(transparent)]
+    FromLettreMessage(#[from] lettre::error::Error),
+    #[error(transparent)]
+    FromSmtpTransport(#[from] lettre::transport::smtp::Error),
+    #[error(transparent)]
+    FromIo(#[from] std::io::Error),
+    #[error(transparent)]
+    FromLoreAPIClient(#[from] crate::lore::lore_api_client::ClientError),
+}
+
+/// Which transport encryption to use when connecting to the configured relay.
+pub enum SmtpEncryption {
+    /// Plain connection upgraded with `STARTTLS`.
+    StartTls,
+    /// TLS from the first byte of the connection.
+    ImplicitTls,
+    /// No encryption; only meant for relays on localhost/a trusted network.
+    None,
+}
+
+/// Connection details for the SMTP relay used to send Reviewed-by replies.
+pub struct SmtpConfig {
+    pub relay_host: String,
+    pub relay_port: u16,
+    pub encryption: SmtpEncryption,
+    pub username: String,
+    pub password: String,
+}
+
+impl SmtpConfig {
+    fn build_transport(&self) -> Result<SmtpTransport, SmtpReplyError> {
+        let credentials = Credentials::new(self.username.clone(), self.password.clone());
+
+        let builder = match self.encryption {
+            SmtpEncryption::ImplicitTls => SmtpTransport::relay(&self.relay_host)?,
+            SmtpEncryption::StartTls => {
+                let tls_parameters = TlsParameters::new(self.relay_host.clone())?;
+                SmtpTransport::builder_dangerous(&self.relay_host).tls(Tls::Required(tls_parameters))
+            }
+            SmtpEncryption::None => {
+                SmtpTransport::builder_dangerous(&self.relay_host).tls(Tls::None)
+            }
+        };
+
+        Ok(builder.port(self.relay_port).credentials(credentials).build())
+    }
+}
+
+/// SMTP counterpart of `prepare_reply_patchset_with_reviewed_by`: generates the same
+/// Reviewed-by reply template and sends each one directly over `smtp_config`'s relay,
+/// rather than returning `git send-email` commands for the caller to run.
+pub fn prepare_reply_patchset_with_reviewed_by_smtp<T>(
+    lore_api_client: &T,
+    smtp_config: &SmtpConfig,
+    tmp_dir: &Path,
+    target_list: &str,
+    patches: &[String],
+    patches_to_reply: &[bool],
+    git_signature: &str,
+    reply_template_config: &ReplyTemplateConfig,
+) -> Result<(), SmtpReplyError>
+where
+    T: PatchHTMLRequest,
+{
+    let transport = smtp_config.build_transport()?;
+
+    static RE_MESSAGE_ID: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(?m)^Message-Id: <(.*?)>"#).unwrap());
+
+    for (i, patch) in patches.iter().enumerate() {
+        if !patches_to_reply[i] {
+            continue;
+        }
+
+        let message_id = RE_MESSAGE_ID
+            .captures(patch)
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .as_str();
+
+        let mut reply = generate_patch_reply_template(patch, reply_template_config);
+        reply.push_str(&format!("\nReviewed-by: {git_signature}\n"));
+
+        let reply_path = tmp_dir.join(format!("{message_id}-reply.mbx"));
+        fs::write(&reply_path, &reply)?;
+
+        let patch_html = lore_api_client.request_patch_html(target_list, message_id)?;
+
+        let message = build_threaded_message(patch, &patch_html, &reply)?;
+        transport.send(&message)?;
+    }
+
+    Ok(())
+}
+
+/// Assembles a properly threaded `lettre::Message` from `reply` (the template
+/// produced by `generate_patch_reply_template`, already carrying the stripped,
+/// `Re: `-prefixed subject) and the `From`/`Message-Id`/`References` headers found
+/// in the original patch, plus the `To`/`Cc` recipients advertised on the patch's
+/// lore page. The header block at the top of `reply` is dropped before it's used
+/// as the message body, since those headers are set as real MIME headers instead.
+fn build_threaded_message(patch: &str, patch_html: &str, reply: &str) -> Result<Message, SmtpReplyError> {
+    static RE_MESSAGE_ID: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?m)^Message-Id: <(.*?)>").unwrap());
+    static RE_REFERENCES: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?m)^References: <(.*?)>").unwrap());
+
+    let from = extract_header(patch, "From").ok_or(SmtpReplyError::MissingHeader("From"))?;
+    let subject = extract_header(reply, "Subject").ok_or(SmtpReplyError::MissingHeader("Subject"))?;
+    let message_id = RE_MESSAGE_ID
+        .captures(patch)
+        .map(|c| c[1].to_owned())
+        .ok_or(SmtpReplyError::MissingHeader("Message-Id"))?;
+    let references = RE_REFERENCES
+        .captures(patch)
+        .map(|c| c[1].to_owned())
+        .unwrap_or_else(|| message_id.clone());
+    let body = strip_header_block(reply);
+
+    let mut builder = Message::builder()
+        .from(from.parse()?)
+        .subject(subject)
+        .in_reply_to(format!("<{message_id}>"))
+        .references(format!("<{references}>"));
+
+    for to in extract_recipients(patch_html, "--to=") {
+        builder = builder.to(to.parse()?);
+    }
+    for cc in extract_recipients(patch_html, "--cc=") {
+        builder = builder.cc(cc.parse()?);
+    }
+
+    Ok(builder.header(ContentType::TEXT_PLAIN).body(body.to_owned())?)
+}
+
+/// Drops the header block `generate_patch_reply_template` writes at the top of the
+/// reply (everything up to the first blank line), leaving just the quoted diff and
+/// the `Reviewed-by` trailer for use as the message body.
+fn strip_header_block(reply: &str) -> &str {
+    reply.split_once("\n\n").map_or("", |(_headers, body)| body)
+}
+
+fn extract_header(patch: &str, header_name: &str) -> Option<String> {
+    let prefix = format!("{header_name}: ");
+    patch
+        .lines()
+        .find(|line| line.starts_with(&prefix))
+        .map(|line| line.trim_start_matches(&prefix).trim().to_owned())
+}
+
+/// Scopes the search to the same `git-send-email(1): ... /path/to/YOUR_REPLY` block
+/// `extract_git_reply_command` (lore_session.rs) parses, rather than the whole page,
+/// since the page also renders the patch's raw diff, which can itself contain
+/// `--to=`/`--cc=`-looking tokens.
+fn extract_recipients(patch_html: &str, option_prefix: &str) -> Vec<String> {
+    static RE_FULL_GIT_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?s)git-send-email\(1\):(.*?)/path/to/YOUR_REPLY"#).unwrap()
+    });
+
+    let Some(send_email_block) = RE_FULL_GIT_COMMAND
+        .captures(patch_html)
+        .and_then(|c| c.get(1))
+    else {
+        return Vec::new();
+    };
+
+    send_email_block
+        .as_str()
+        .split_whitespace()
+        .filter_map(|token| token.strip_prefix(option_prefix))
+        .map(|address| address.trim_matches(|c| c == '"' || c == '\'').to_owned())
+        .collect()
+}
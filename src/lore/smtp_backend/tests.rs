@@ -0,0 +1,106 @@
+use super::*;
+
+const PATCH: &str = "\
+From: Jane Dev <jane@example.com>
+Subject: [PATCH v2 1/3] fix the thing
+Date: Mon, 1 Jan 2024 00:00:00 +0000
+Message-Id: <abc123@example.com>
+References: <cover000@example.com>
+
+Some commit message.
+---
+diff --git a/foo.c b/foo.c
+index 0000000..1111111 100644
+--- a/foo.c
++++ b/foo.c
+@@ -1 +1 @@
+-old
++new
+";
+
+const REPLY: &str = "\
+Subject: Re: fix the thing
+
+> Some commit message.
+> ---
+> diff --git a/foo.c b/foo.c
+
+Reviewed-by: Jane Reviewer <reviewer@example.com>
+";
+
+const PATCH_HTML: &str = "\
+<pre>
+diff --git a/contrib/send-patch.sh b/contrib/send-patch.sh
++# example: --to=\"evil@example.com\"
+</pre>
+<pre>
+git-send-email(1):
+
+git send-email \\
+    --to=\"maintainer@example.com\" \\
+    --cc=\"list@example.com\" \\
+    /path/to/YOUR_REPLY
+</pre>
+";
+
+#[test]
+fn uses_the_already_stripped_subject_from_the_rendered_reply() {
+    let message = build_threaded_message(PATCH, PATCH_HTML, REPLY).unwrap();
+    let raw = String::from_utf8(message.formatted()).unwrap();
+
+    assert!(raw.contains("Subject: Re: fix the thing"));
+    assert!(!raw.contains("[PATCH v2 1/3]"));
+}
+
+#[test]
+fn body_does_not_duplicate_the_header_block() {
+    let message = build_threaded_message(PATCH, PATCH_HTML, REPLY).unwrap();
+    let raw = String::from_utf8(message.formatted()).unwrap();
+
+    let body = raw.split_once("\r\n\r\n").map_or("", |(_headers, body)| body);
+
+    assert!(!body.contains("Subject:"));
+    assert!(body.contains("Reviewed-by: Jane Reviewer"));
+}
+
+#[test]
+fn extracts_to_and_cc_recipients_from_the_lore_send_email_block() {
+    assert_eq!(
+        extract_recipients(PATCH_HTML, "--to="),
+        vec!["maintainer@example.com".to_owned()]
+    );
+    assert_eq!(
+        extract_recipients(PATCH_HTML, "--cc="),
+        vec!["list@example.com".to_owned()]
+    );
+}
+
+#[test]
+fn does_not_pick_up_to_cc_looking_tokens_outside_the_send_email_block() {
+    let recipients = extract_recipients(PATCH_HTML, "--to=");
+
+    assert!(!recipients.contains(&"evil@example.com".to_owned()));
+}
+
+#[test]
+fn in_reply_to_and_references_headers_are_wrapped_in_exactly_one_pair_of_brackets() {
+    let message = build_threaded_message(PATCH, PATCH_HTML, REPLY).unwrap();
+    let raw = String::from_utf8(message.formatted()).unwrap();
+
+    assert!(raw.contains("In-Reply-To: <abc123@example.com>"));
+    assert!(raw.contains("References: <cover000@example.com>"));
+    assert!(!raw.contains("<<abc123@example.com>>"));
+}
+
+#[test]
+fn strip_header_block_drops_everything_up_to_the_first_blank_line() {
+    assert_eq!(
+        strip_header_block("Subject: Re: x\n\n> quoted\n"),
+        "> quoted\n"
+    );
+}
+
+#[test]
+fn strip_header_block_returns_empty_when_there_is_no_blank_line() {
+    assert_eq!(strip_header_block("Subject: Re: x\n"), "");
+}